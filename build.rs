@@ -1,7 +1,70 @@
 use std::{env, process::Command};
 use build_target::{Arch, Os};
 
+#[cfg(feature = "download-tflite")]
+use std::io::Read;
+#[cfg(feature = "download-tflite")]
+use sha2::{Digest, Sha256};
+
+/// Strategy for obtaining the prebuilt TFLite static libs, mirroring `ort`'s
+/// `ORT_STRATEGY` env var. Defaults to `Compile` (today's behavior) when the
+/// env var isn't set, same as before this var existed.
+enum TfliteStrategy {
+    /// Download the matching prebuilt archive into `OUT_DIR` (requires the
+    /// `download-tflite` feature).
+    Download,
+    /// Link against a system-provided build (see `DTLN_TFLITE_LIB_DIR`).
+    System,
+    /// Extract the archive vendored under `./tflite/` if present, otherwise
+    /// build from source via cmake.
+    Compile,
+}
+
+impl TfliteStrategy {
+    fn from_env() -> Self {
+        match env::var("DTLN_TFLITE_STRATEGY").as_deref() {
+            Ok("download") => TfliteStrategy::Download,
+            Ok("system") => TfliteStrategy::System,
+            Ok("compile") => TfliteStrategy::Compile,
+            Ok(other) => panic!(
+                "Unknown DTLN_TFLITE_STRATEGY `{}`, expected one of: download, system, compile",
+                other
+            ),
+            Err(_) => TfliteStrategy::Compile,
+        }
+    }
+}
+
 fn main() {
+    // Nothing in this file is needed (or safe to run -- there may be no
+    // cmake/libclang toolchain at all) unless the tflite-backend feature
+    // actually pulls in the `tflite` crate.
+    if !cfg!(feature = "tflite-backend") {
+        return;
+    }
+
+    println!("cargo:rerun-if-env-changed=DTLN_TFLITE_STRATEGY");
+    println!("cargo:rerun-if-env-changed=DTLN_TFLITE_LIB_DIR");
+
+    // A user-supplied install (e.g. a distro package or a locally-built
+    // TFLite) takes priority over everything else: skip archive extraction
+    // and cmake entirely and link straight from the given directory. This is
+    // also what `DTLN_TFLITE_STRATEGY=system` means, so require it there.
+    let strategy = TfliteStrategy::from_env();
+    match env::var("DTLN_TFLITE_LIB_DIR") {
+        Ok(lib_dir) => {
+            link_static_libs(&lib_dir);
+            return;
+        }
+        Err(_) if matches!(strategy, TfliteStrategy::System) => {
+            panic!(
+                "DTLN_TFLITE_STRATEGY=system requires DTLN_TFLITE_LIB_DIR to point at a \
+                 directory containing the prebuilt libtensorflowlite*.a/.lib"
+            );
+        }
+        Err(_) => {}
+    }
+
     let target_arch = build_target::target_arch().unwrap();
     let target_os = build_target::target_os().unwrap();
 
@@ -42,6 +105,14 @@ fn main() {
                 eprintln!("Warning: Failed to extract prebuilt TFLite, trying cmake...");
                 build_with_cmake();
             }
+        } else if matches!(strategy, TfliteStrategy::Download) && download_prebuilt(archive)
+        {
+            // Downloaded (and extracted) successfully into OUT_DIR and
+            // already linked from there -- don't fall through to the
+            // unconditional link_static_libs() below, which would also
+            // link whatever (possibly stale/mismatched) archive happens to
+            // be vendored under ./tflite/lib.
+            return;
         } else {
             eprintln!("Warning: Prebuilt archive not found at {}, trying cmake...", archive_path);
             build_with_cmake();
@@ -52,10 +123,26 @@ fn main() {
     }
 
     let root_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
-    println!("cargo:rustc-link-search=native={}/tflite/lib/", root_dir);
+    link_static_libs(&format!("{}/tflite/lib", root_dir));
+}
 
-    // Link to all archives in lib directory
-    if let Ok(entries) = std::fs::read_dir(std::format!("{}/tflite/lib", root_dir)) {
+/// Archives whose op-kernel registration objects the platform linker would
+/// otherwise drop as "unreferenced" (they register themselves via static
+/// initializers, not direct calls). Only these get whole-archive treatment,
+/// on by default -- see the `no-whole-archive-tflite` feature to opt out
+/// (e.g. when linking a trimmed op set where the dropped symbols are known
+/// to be unused).
+const WHOLE_ARCHIVE_LIBS: &[&str] = &["tensorflow-lite", "tensorflowlite"];
+
+/// Emit `cargo:rustc-link-search`/`cargo:rustc-link-lib=static` for every
+/// `*.a`/`*.lib` found directly under `lib_dir`. The TFLite kernel archive(s)
+/// are force-loaded in full by default (see [`WHOLE_ARCHIVE_LIBS`]) so
+/// kernels that register via static initializers survive a linker that
+/// would otherwise drop their "unreferenced" object files.
+fn link_static_libs(lib_dir: &str) {
+    println!("cargo:rustc-link-search=native={}", lib_dir);
+
+    if let Ok(entries) = std::fs::read_dir(lib_dir) {
         for entry in entries.flatten() {
             let path = entry.path();
             let extension = path.extension();
@@ -66,8 +153,14 @@ fn main() {
                     // Handle both lib prefix (Unix) and no prefix (Windows)
                     let lib_name = lib_name.strip_prefix("lib").unwrap_or(lib_name);
 
-                    // Always use static linking for better portability
-                    println!("cargo:rustc-link-lib=static={}", lib_name);
+                    if !cfg!(feature = "no-whole-archive-tflite")
+                        && WHOLE_ARCHIVE_LIBS.contains(&lib_name)
+                    {
+                        whole_archive_link(&path, lib_name);
+                    } else {
+                        // Always use static linking for better portability
+                        println!("cargo:rustc-link-lib=static={}", lib_name);
+                    }
                 }
                 _ => {}
             }
@@ -75,14 +168,183 @@ fn main() {
     }
 }
 
+/// Force-load every object in `lib_path`, using whichever whole-archive
+/// flavor the target linker understands.
+fn whole_archive_link(lib_path: &std::path::Path, lib_name: &str) {
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let target_env = env::var("CARGO_CFG_TARGET_ENV").unwrap_or_default();
+
+    if target_env == "msvc" {
+        println!("cargo:rustc-link-arg=/WHOLEARCHIVE:{}", lib_path.display());
+        println!("cargo:rustc-link-lib=static={}", lib_name);
+    } else if target_os == "macos" || target_os == "ios" {
+        println!("cargo:rustc-link-arg=-Wl,-force_load,{}", lib_path.display());
+    } else {
+        // GNU/ELF linkers (Linux, Android, most embedded targets). Cargo
+        // appends all `rustc-link-arg` output after the `-l` flags it
+        // generates from `rustc-link-lib`, so emitting --whole-archive,
+        // the -l, and --no-whole-archive as separate directives would put
+        // the toggle pair at the tail of the link line instead of around
+        // the library. Pass the archive's absolute path directly inside a
+        // single --whole-archive/--no-whole-archive pair instead.
+        println!(
+            "cargo:rustc-link-arg=-Wl,--whole-archive,{},--no-whole-archive",
+            lib_path.display()
+        );
+    }
+}
+
 fn build_with_cmake() {
-    let cmake_result = Command::new("cmake")
+    let mut cmake = Command::new("cmake");
+    cmake
         .current_dir("tflite")
         .arg(".")
-        .arg("-DCMAKE_BUILD_TYPE=Release")
-        .status();
+        .arg("-DCMAKE_BUILD_TYPE=Release");
+
+    add_cross_compile_args(&mut cmake);
+
+    let cmake_result = cmake.status();
 
     if cmake_result.is_err() {
         panic!("Failed to run cmake. Please ensure cmake is installed or provide prebuilt TFLite libraries.");
     }
 }
+
+/// When cross-compiling, cmake has no idea which compiler/sysroot to use
+/// unless we tell it -- it otherwise happily picks up the host toolchain and
+/// produces libs for the wrong architecture. Modeled on how pico-tflmicro-sys
+/// resolves this: ask the `cc` crate for the target compiler it would use,
+/// query that compiler's sysroot, and hand both to cmake.
+///
+/// An explicit `DTLN_CMAKE_TOOLCHAIN` env var overrides all of this with a
+/// CMake toolchain file, for targets where compiler/sysroot discovery isn't
+/// enough (e.g. bespoke embedded SDKs).
+fn add_cross_compile_args(cmake: &mut Command) {
+    println!("cargo:rerun-if-env-changed=DTLN_CMAKE_TOOLCHAIN");
+
+    if let Ok(toolchain_file) = env::var("DTLN_CMAKE_TOOLCHAIN") {
+        cmake.arg(format!("-DCMAKE_TOOLCHAIN_FILE={}", toolchain_file));
+        return;
+    }
+
+    let host = env::var("HOST").unwrap();
+    let target = env::var("TARGET").unwrap();
+    if host == target {
+        return;
+    }
+
+    let compiler = cc::Build::new().target(&target).host(&host).get_compiler();
+    let compiler_path = compiler.path();
+
+    let sysroot_output = Command::new(compiler_path).arg("--print-sysroot").output();
+    if let Ok(output) = sysroot_output {
+        let sysroot = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !sysroot.is_empty() {
+            cmake.arg(format!("-DCMAKE_SYSROOT={}", sysroot));
+        }
+    }
+
+    cmake.arg(format!("-DCMAKE_C_COMPILER={}", compiler_path.display()));
+    cmake.arg(format!("-DCMAKE_CXX_COMPILER={}", compiler_path.display()));
+
+    let target_arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+    cmake.arg(format!("-DCMAKE_SYSTEM_PROCESSOR={}", target_arch));
+
+    // CMake only flips CMAKE_CROSSCOMPILING on when CMAKE_SYSTEM_NAME is
+    // given explicitly and differs from the host -- without it TFLite's
+    // CMakeLists configures natively and misses its cross-compiling
+    // branches (e.g. building flatc for the host instead of the target).
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap();
+    let system_name = match target_os.as_str() {
+        "linux" | "android" => "Linux",
+        "macos" => "Darwin",
+        "ios" => "iOS",
+        "windows" => "Windows",
+        _ => "Generic",
+    };
+    cmake.arg(format!("-DCMAKE_SYSTEM_NAME={}", system_name));
+}
+
+/// Fetch `tflite-prebuilt.<os>.<arch>.tar.bz2` into `OUT_DIR`, check it
+/// against the published checksum, extract it there and link from
+/// `OUT_DIR/lib`. Returns
+/// `false` (without touching anything) when the `download-tflite` feature is
+/// disabled, so docs.rs and offline builds fall through to cmake untouched.
+#[cfg(feature = "download-tflite")]
+fn download_prebuilt(archive: &str) -> bool {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let url = format!(
+        "https://github.com/hayatialikeles/dtln-rs/releases/latest/download/{}",
+        archive
+    );
+    let checksum_url = format!("{}.sha256", url);
+
+    let archive_bytes = match ureq::get(&url).call().and_then(|resp| {
+        let mut buf = Vec::new();
+        resp.into_reader()
+            .read_to_end(&mut buf)
+            .map(|_| buf)
+            .map_err(|e| e.into())
+    }) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!("Warning: Failed to download {}: {}", url, e);
+            return false;
+        }
+    };
+
+    let expected_checksum = match ureq::get(&checksum_url).call().and_then(|resp| {
+        resp.into_string().map_err(|e| e.into())
+    }) {
+        Ok(checksum) => checksum.split_whitespace().next().unwrap_or("").to_string(),
+        Err(e) => {
+            eprintln!("Warning: Failed to fetch checksum {}: {}", checksum_url, e);
+            return false;
+        }
+    };
+
+    let mut hasher = Sha256::new();
+    hasher.update(&archive_bytes);
+    let actual_checksum = format!("{:x}", hasher.finalize());
+
+    if actual_checksum != expected_checksum {
+        eprintln!(
+            "Warning: Checksum mismatch for {} (expected {}, got {})",
+            archive, expected_checksum, actual_checksum
+        );
+        return false;
+    }
+
+    let archive_path = format!("{}/{}", out_dir, archive);
+    if std::fs::write(&archive_path, &archive_bytes).is_err() {
+        eprintln!("Warning: Failed to write downloaded archive to {}", archive_path);
+        return false;
+    }
+
+    let extract_result = Command::new("tar")
+        .arg("-xjf")
+        .arg(&archive_path)
+        .arg("-C")
+        .arg(&out_dir)
+        .status();
+
+    match extract_result {
+        Ok(status) if status.success() => {
+            link_static_libs(&format!("{}/lib", out_dir));
+            true
+        }
+        _ => {
+            eprintln!("Warning: Failed to extract downloaded archive {}", archive_path);
+            false
+        }
+    }
+}
+
+#[cfg(not(feature = "download-tflite"))]
+fn download_prebuilt(_archive: &str) -> bool {
+    eprintln!(
+        "Warning: DTLN_TFLITE_STRATEGY=download requires the `download-tflite` feature, \
+         falling back to cmake..."
+    );
+    false
+}
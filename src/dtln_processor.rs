@@ -0,0 +1,221 @@
+//! Block-based DTLN denoising engine used by callers that feed in PCM in
+//! fixed-size chunks (e.g. a live audio callback) and want denoised samples
+//! back without blocking on the model.
+//!
+//! `process_block` is currently a stub: it's missing the STFT/ISTFT framing
+//! real DTLN inference needs, so it won't produce correct output (or even
+//! matching tensor shapes) against the bundled models. See its doc comment.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use anyhow::{Context, Result};
+
+use crate::inference_backend::{InferenceBackend, LstmState};
+
+#[cfg(feature = "tflite-backend")]
+const STAGE1_MODEL_PATH: &str = "models/dtln_stage1.tflite";
+#[cfg(feature = "tflite-backend")]
+const STAGE2_MODEL_PATH: &str = "models/dtln_stage2.tflite";
+
+/// Result of feeding one block of PCM into a [`DtlnProcessEngine`].
+pub struct DenoiseResult {
+    /// Denoised samples ready so far. May be shorter than the input block
+    /// (or empty) since inference runs on a background thread.
+    pub samples: Vec<i32>,
+    /// `true` if the background worker had no denoised output ready for this
+    /// call, i.e. the caller is feeding blocks faster than inference can
+    /// keep up.
+    pub processor_starved: bool,
+}
+
+/// Common surface for a DTLN processing engine, so callers (and tests) can
+/// swap in a different engine (e.g. a synchronous one) without touching the
+/// call sites in `main.rs`.
+pub trait DtlnProcessEngine {
+    fn denoise(&mut self, pcm: &[i32]) -> Result<DenoiseResult>;
+    fn stop(&mut self);
+}
+
+/// Runs DTLN inference on a background thread so `denoise` never blocks the
+/// caller on model latency. Input blocks are queued for the worker; each
+/// call to `denoise` returns whatever output the worker has finished since
+/// the last call.
+pub struct DtlnDeferredProcessor {
+    input_tx: mpsc::Sender<Vec<i32>>,
+    output_rx: mpsc::Receiver<Vec<i32>>,
+    worker: Option<JoinHandle<()>>,
+    stopping: Arc<AtomicBool>,
+}
+
+impl DtlnDeferredProcessor {
+    /// Build a processor using the default TFLite backend and the DTLN
+    /// models bundled with the crate.
+    #[cfg(feature = "tflite-backend")]
+    pub fn new() -> Result<Self> {
+        let backend = crate::inference_backend::tflite_backend::TfliteBackend::new(
+            std::path::Path::new(STAGE1_MODEL_PATH),
+            std::path::Path::new(STAGE2_MODEL_PATH),
+        )
+        .context("loading bundled DTLN TFLite models")?;
+        Self::with_backend(Box::new(backend))
+    }
+
+    /// Build a processor around an arbitrary [`InferenceBackend`], e.g. the
+    /// ONNX Runtime backend behind the `ort-backend` feature.
+    pub fn with_backend(backend: Box<dyn InferenceBackend>) -> Result<Self> {
+        let (input_tx, input_rx) = mpsc::channel::<Vec<i32>>();
+        let (output_tx, output_rx) = mpsc::channel::<Vec<i32>>();
+        let stopping = Arc::new(AtomicBool::new(false));
+        let worker_stopping = stopping.clone();
+
+        let worker = thread::spawn(move || {
+            let mut backend = backend;
+            let mut stage1_state = LstmState::default();
+            let mut stage2_state = LstmState::default();
+
+            while let Ok(block) = input_rx.recv() {
+                if worker_stopping.load(Ordering::Relaxed) {
+                    break;
+                }
+                match process_block(&mut *backend, &block, &mut stage1_state, &mut stage2_state) {
+                    Ok(denoised) => {
+                        if output_tx.send(denoised).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => eprintln!("DTLN inference error: {}", e),
+                }
+            }
+        });
+
+        Ok(Self {
+            input_tx,
+            output_rx,
+            worker: Some(worker),
+            stopping,
+        })
+    }
+}
+
+impl DtlnProcessEngine for DtlnDeferredProcessor {
+    fn denoise(&mut self, pcm: &[i32]) -> Result<DenoiseResult> {
+        self.input_tx
+            .send(pcm.to_vec())
+            .context("DTLN worker thread has already stopped")?;
+
+        let mut samples = Vec::new();
+        let mut processor_starved = true;
+        while let Ok(block) = self.output_rx.try_recv() {
+            samples.extend(block);
+            processor_starved = false;
+        }
+
+        Ok(DenoiseResult {
+            samples,
+            processor_starved,
+        })
+    }
+
+    fn stop(&mut self) {
+        self.stopping.store(true, Ordering::Relaxed);
+        // Dropping the sender closes the channel so the worker's `recv`
+        // unblocks even if no more blocks are sent.
+        let (closed_tx, _) = mpsc::channel();
+        drop(std::mem::replace(&mut self.input_tx, closed_tx));
+
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Run one input block through both DTLN stages, carrying the LSTM states
+/// across calls. `stage1` estimates a spectral mask from the block and
+/// `stage2` refines the masked signal in the time domain.
+///
+/// STUB: real DTLN stage 1 takes an STFT magnitude spectrum and its mask
+/// must be applied to the complex spectrum and inverse-transformed (with
+/// overlap-add) before stage 2 sees time-domain samples. This instead feeds
+/// `stage1`/`stage2` the raw PCM block directly, with no STFT/ISTFT framing
+/// -- it round-trips through a backend that shares input/output shapes, but
+/// won't produce correct output (or even matching tensor shapes) against
+/// the bundled DTLN models, whose stage1 input is FFT-bin-sized, not
+/// block-sized. Needs real STFT/ISTFT framing before this is usable against
+/// those models.
+fn process_block(
+    backend: &mut dyn InferenceBackend,
+    block: &[i32],
+    stage1_state: &mut LstmState,
+    stage2_state: &mut LstmState,
+) -> Result<Vec<i32>> {
+    let magnitude: Vec<f32> = block.iter().map(|&s| s as f32 / i32::MAX as f32).collect();
+
+    let (mask, next_stage1_state) = backend.run_stage1(&magnitude, stage1_state)?;
+    *stage1_state = next_stage1_state;
+
+    let masked: Vec<f32> = magnitude
+        .iter()
+        .zip(mask.iter().cycle())
+        .map(|(sample, m)| sample * m)
+        .collect();
+
+    let (denoised, next_stage2_state) = backend.run_stage2(&masked, stage2_state)?;
+    *stage2_state = next_stage2_state;
+
+    Ok(denoised
+        .iter()
+        .map(|&s| (s * i32::MAX as f32) as i32)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Backend that returns an all-ones mask from stage 1, passes the stage 2
+    /// frame straight through as its output, and appends one element to the
+    /// state each call, so tests can check that
+    /// `process_block` threads state correctly and round-trips the PCM/float
+    /// scaling without needing a real model.
+    struct PassthroughBackend;
+
+    impl InferenceBackend for PassthroughBackend {
+        fn run_stage1(&mut self, magnitude: &[f32], state: &LstmState) -> Result<(Vec<f32>, LstmState)> {
+            let mask = vec![1.0; magnitude.len()];
+            let mut next_state = state.clone();
+            next_state.push(1.0);
+            Ok((mask, next_state))
+        }
+
+        fn run_stage2(&mut self, frame: &[f32], state: &LstmState) -> Result<(Vec<f32>, LstmState)> {
+            let mut next_state = state.clone();
+            next_state.push(2.0);
+            Ok((frame.to_vec(), next_state))
+        }
+    }
+
+    #[test]
+    fn process_block_round_trips_pcm_and_carries_state() {
+        let mut backend = PassthroughBackend;
+        let mut stage1_state = LstmState::new();
+        let mut stage2_state = LstmState::new();
+
+        let block = vec![0, i32::MAX / 2, i32::MIN / 2];
+        let out = process_block(&mut backend, &block, &mut stage1_state, &mut stage2_state).unwrap();
+
+        assert_eq!(out.len(), block.len());
+        for (input, output) in block.iter().zip(out.iter()) {
+            assert!((input - output).abs() <= 1, "{} vs {}", input, output);
+        }
+
+        assert_eq!(stage1_state, vec![1.0]);
+        assert_eq!(stage2_state, vec![2.0]);
+
+        process_block(&mut backend, &block, &mut stage1_state, &mut stage2_state).unwrap();
+        assert_eq!(stage1_state, vec![1.0, 1.0]);
+        assert_eq!(stage2_state, vec![2.0, 2.0]);
+    }
+}
@@ -0,0 +1,142 @@
+//! Minimal 16-bit PCM WAV file I/O for the sample CLI in `main.rs`: read a
+//! mono WAV into the `i32`-per-sample buffers [`crate::dtln_processor`]
+//! operates on, and write denoised output back out the same way.
+
+use std::convert::TryInto;
+use std::fs::File;
+use std::io::{Read, Write};
+
+use anyhow::{ensure, Context, Result};
+
+/// Full-scale ratio between an `i16` WAV sample and the `i32` representation
+/// the DTLN engine works in (see `process_block`'s `/ i32::MAX` scaling).
+const I16_TO_I32: i32 = i32::MAX / i16::MAX as i32;
+
+/// Read a 16-bit PCM mono WAV file at `path`, appending each sample (widened
+/// to `i32`) to `samples`. Returns the file's sample rate. Panics on any I/O
+/// or format error, since the CLI has no way to recover from an unreadable
+/// input file.
+pub fn read_wav_to_pcm32(path: &str, samples: &mut Vec<i32>) -> u32 {
+    read_wav_to_pcm32_inner(path, samples)
+        .unwrap_or_else(|e| panic!("failed to read wav file {}: {}", path, e))
+}
+
+fn read_wav_to_pcm32_inner(path: &str, samples: &mut Vec<i32>) -> Result<u32> {
+    let mut bytes = Vec::new();
+    File::open(path)
+        .with_context(|| format!("opening {}", path))?
+        .read_to_end(&mut bytes)
+        .with_context(|| format!("reading {}", path))?;
+
+    ensure!(
+        bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WAVE",
+        "{} is not a RIFF/WAVE file",
+        path
+    );
+
+    let mut sample_rate = None;
+    let mut bits_per_sample = 0u16;
+    let mut pos = 12;
+
+    while pos + 8 <= bytes.len() {
+        let chunk_id = &bytes[pos..pos + 4];
+        let chunk_size = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        let chunk_start = pos + 8;
+        ensure!(chunk_start + chunk_size <= bytes.len(), "{} has a truncated {:?} chunk", path, chunk_id);
+
+        match chunk_id {
+            b"fmt " => {
+                sample_rate = Some(u32::from_le_bytes(
+                    bytes[chunk_start + 4..chunk_start + 8].try_into().unwrap(),
+                ));
+                bits_per_sample =
+                    u16::from_le_bytes(bytes[chunk_start + 14..chunk_start + 16].try_into().unwrap());
+            }
+            b"data" => {
+                ensure!(
+                    bits_per_sample == 16,
+                    "{} is {}-bit PCM, only 16-bit is supported",
+                    path,
+                    bits_per_sample
+                );
+                let data = &bytes[chunk_start..chunk_start + chunk_size];
+                samples.extend(
+                    data.chunks_exact(2)
+                        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]) as i32 * I16_TO_I32),
+                );
+            }
+            _ => {}
+        }
+
+        // Chunks are word-aligned: an odd-sized chunk is followed by a pad byte.
+        pos = chunk_start + chunk_size + (chunk_size % 2);
+    }
+
+    sample_rate.ok_or_else(|| anyhow::anyhow!("{} has no fmt chunk", path))
+}
+
+/// Write `samples` (the same `i32`-per-sample representation DTLN works in)
+/// to `path` as a 16-bit PCM mono WAV at `sample_rate`.
+pub fn write_pcm32_to_wav(samples: Vec<i32>, path: &str, sample_rate: u32) -> Result<()> {
+    const BITS_PER_SAMPLE: u16 = 16;
+    const CHANNELS: u16 = 1;
+
+    let block_align = CHANNELS * BITS_PER_SAMPLE / 8;
+    let byte_rate = sample_rate * block_align as u32;
+    let data_size = samples.len() * block_align as usize;
+    ensure!(
+        data_size <= u32::MAX as usize,
+        "{} has too many samples to fit in a WAV data chunk",
+        path
+    );
+    let data_size = data_size as u32;
+
+    let mut file = File::create(path).with_context(|| format!("creating {}", path))?;
+
+    file.write_all(b"RIFF")?;
+    file.write_all(&(36 + data_size).to_le_bytes())?;
+    file.write_all(b"WAVE")?;
+
+    file.write_all(b"fmt ")?;
+    file.write_all(&16u32.to_le_bytes())?;
+    file.write_all(&1u16.to_le_bytes())?; // PCM
+    file.write_all(&CHANNELS.to_le_bytes())?;
+    file.write_all(&sample_rate.to_le_bytes())?;
+    file.write_all(&byte_rate.to_le_bytes())?;
+    file.write_all(&block_align.to_le_bytes())?;
+    file.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+
+    file.write_all(b"data")?;
+    file.write_all(&data_size.to_le_bytes())?;
+    for sample in samples {
+        let narrowed = (sample / I16_TO_I32) as i16;
+        file.write_all(&narrowed.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_then_read_round_trips_samples_and_sample_rate() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("dtln_rs_test_{}.wav", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        // Only exact multiples of I16_TO_I32 round-trip losslessly through
+        // the 16-bit narrowing in write_pcm32_to_wav.
+        let samples = vec![0, I16_TO_I32, -I16_TO_I32, I16_TO_I32 * 3];
+        write_pcm32_to_wav(samples.clone(), path, 16000).unwrap();
+
+        let mut read_back = Vec::new();
+        let sample_rate = read_wav_to_pcm32(path, &mut read_back);
+
+        assert_eq!(sample_rate, 16000);
+        assert_eq!(read_back, samples);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,172 @@
+//! The pluggable half of the DTLN pipeline: the two stateful model
+//! invocations that make up one inference pass, abstracted behind
+//! [`InferenceBackend`] so [`crate::dtln_processor`] doesn't care whether
+//! they run through TensorFlow Lite or ONNX Runtime.
+
+use anyhow::Result;
+
+/// Flattened LSTM hidden/cell state carried between successive calls to a
+/// stage. Backends own the exact tensor shape; callers just thread the
+/// buffer through.
+pub type LstmState = Vec<f32>;
+
+/// The two stages of the DTLN graph. `run_stage1` takes the STFT
+/// magnitude of a frame and returns an estimated mask; `run_stage2` takes the
+/// masked time-domain frame and returns the denoised frame. Both carry their
+/// own LSTM state across calls rather than owning it, so callers can keep
+/// state alive for as long as a stream is open.
+pub trait InferenceBackend: Send {
+    fn run_stage1(&mut self, magnitude: &[f32], state: &LstmState) -> Result<(Vec<f32>, LstmState)>;
+    fn run_stage2(&mut self, frame: &[f32], state: &LstmState) -> Result<(Vec<f32>, LstmState)>;
+}
+
+#[cfg(feature = "tflite-backend")]
+pub mod tflite_backend {
+    use super::*;
+    use anyhow::Context;
+    use std::path::Path;
+    use tflite::ops::builtin::BuiltinOpResolver;
+    use tflite::{FlatBufferModel, Interpreter, InterpreterBuilder};
+
+    /// Default backend: the two DTLN stage models run through TensorFlow
+    /// Lite, same as before this module was split out.
+    pub struct TfliteBackend {
+        stage1: Interpreter<'static, BuiltinOpResolver>,
+        stage2: Interpreter<'static, BuiltinOpResolver>,
+        // The models must outlive the interpreters that borrow them, so they
+        // are declared (and therefore dropped) after the interpreters.
+        _stage1_model: FlatBufferModel,
+        _stage2_model: FlatBufferModel,
+    }
+
+    impl TfliteBackend {
+        pub fn new(stage1_model_path: &Path, stage2_model_path: &Path) -> Result<Self> {
+            let stage1_model = FlatBufferModel::build_from_file(stage1_model_path)
+                .with_context(|| format!("loading stage 1 model from {:?}", stage1_model_path))?;
+            let stage2_model = FlatBufferModel::build_from_file(stage2_model_path)
+                .with_context(|| format!("loading stage 2 model from {:?}", stage2_model_path))?;
+
+            let resolver = BuiltinOpResolver::default();
+            let mut stage1 = InterpreterBuilder::new(&stage1_model, &resolver)?.build()?;
+            let mut stage2 = InterpreterBuilder::new(&stage2_model, &resolver)?.build()?;
+            stage1.allocate_tensors()?;
+            stage2.allocate_tensors()?;
+
+            Ok(Self {
+                _stage1_model: stage1_model,
+                _stage2_model: stage2_model,
+                stage1,
+                stage2,
+            })
+        }
+    }
+
+    impl InferenceBackend for TfliteBackend {
+        fn run_stage1(&mut self, magnitude: &[f32], state: &LstmState) -> Result<(Vec<f32>, LstmState)> {
+            run_stateful(&mut self.stage1, magnitude, state)
+        }
+
+        fn run_stage2(&mut self, frame: &[f32], state: &LstmState) -> Result<(Vec<f32>, LstmState)> {
+            run_stateful(&mut self.stage2, frame, state)
+        }
+    }
+
+    /// Copy `input`/`state` into the interpreter's input tensors, invoke it,
+    /// and copy the main output and the updated state back out. DTLN's
+    /// exported stage models take two inputs (frame, state) and produce two
+    /// outputs (frame, state) in that order.
+    fn run_stateful(
+        interpreter: &mut Interpreter<'static, BuiltinOpResolver>,
+        input: &[f32],
+        state: &LstmState,
+    ) -> Result<(Vec<f32>, LstmState)> {
+        let input_indices = interpreter.inputs().to_vec();
+        let output_indices = interpreter.outputs().to_vec();
+
+        copy_into_tensor(interpreter, input_indices[0], input)?;
+        copy_into_tensor(interpreter, input_indices[1], state)?;
+
+        interpreter.invoke()?;
+
+        let output = interpreter.tensor_data::<f32>(output_indices[0])?.to_vec();
+        let next_state = interpreter.tensor_data::<f32>(output_indices[1])?.to_vec();
+        Ok((output, next_state))
+    }
+
+    /// Copy `data` into tensor `index`, erroring instead of panicking when
+    /// the caller's buffer doesn't match the allocated tensor size -- e.g.
+    /// the short final block produced when an input file's length isn't an
+    /// exact multiple of the caller's block size.
+    fn copy_into_tensor(
+        interpreter: &mut Interpreter<'static, BuiltinOpResolver>,
+        index: i32,
+        data: &[f32],
+    ) -> Result<()> {
+        let tensor = interpreter.tensor_data_mut::<f32>(index)?;
+        anyhow::ensure!(
+            tensor.len() == data.len(),
+            "tensor {} expects {} elements, got {}",
+            index,
+            tensor.len(),
+            data.len()
+        );
+        tensor.copy_from_slice(data);
+        Ok(())
+    }
+}
+
+/// ONNX Runtime backend for the published DTLN ONNX export, selected via the
+/// `ort-backend` feature. Avoids the TFLite C++ build on platforms where a
+/// prebuilt ONNX Runtime is easier to come by, and opens the door to
+/// execution-provider acceleration (CUDA, CoreML, DirectML, ...).
+///
+/// `ort` is pulled in with `default-features = false`, so this crate does
+/// not bundle a working link strategy: point `ORT_LIB_LOCATION` at an
+/// ONNX Runtime build/install, or add `ort/download-binaries` in your own
+/// `Cargo.toml`, before linking anything against `ort-backend`.
+#[cfg(feature = "ort-backend")]
+pub mod ort_backend {
+    use super::*;
+    use ort::inputs;
+    use ort::session::Session;
+    use ort::value::Tensor;
+    use std::path::Path;
+
+    pub struct OrtBackend {
+        stage1: Session,
+        stage2: Session,
+    }
+
+    impl OrtBackend {
+        pub fn new(stage1_onnx_path: &Path, stage2_onnx_path: &Path) -> Result<Self> {
+            let stage1 = Session::builder()?.commit_from_file(stage1_onnx_path)?;
+            let stage2 = Session::builder()?.commit_from_file(stage2_onnx_path)?;
+            Ok(Self { stage1, stage2 })
+        }
+    }
+
+    impl InferenceBackend for OrtBackend {
+        fn run_stage1(&mut self, magnitude: &[f32], state: &LstmState) -> Result<(Vec<f32>, LstmState)> {
+            run_stateful(&mut self.stage1, magnitude, state)
+        }
+
+        fn run_stage2(&mut self, frame: &[f32], state: &LstmState) -> Result<(Vec<f32>, LstmState)> {
+            run_stateful(&mut self.stage2, frame, state)
+        }
+    }
+
+    /// Run one (frame, state) pair through `session`, returning the main
+    /// output and the updated state. DTLN's exported stage models take two
+    /// 1-D float inputs (frame, state) and produce two 1-D float outputs
+    /// (frame, state) in that order.
+    fn run_stateful(session: &mut Session, input: &[f32], state: &LstmState) -> Result<(Vec<f32>, LstmState)> {
+        let input_tensor = Tensor::from_array(([input.len() as i64], input.to_vec()))?;
+        let state_tensor = Tensor::from_array(([state.len() as i64], state.clone()))?;
+
+        let outputs = session.run(inputs![input_tensor, state_tensor])?;
+
+        let (_, frame) = outputs[0].try_extract_tensor::<f32>()?;
+        let (_, next_state) = outputs[1].try_extract_tensor::<f32>()?;
+        Ok((frame.to_vec(), next_state.to_vec()))
+    }
+}
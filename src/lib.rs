@@ -0,0 +1,3 @@
+pub mod dtln_processor;
+pub mod dtln_utilities;
+pub mod inference_backend;
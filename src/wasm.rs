@@ -0,0 +1,71 @@
+//! Exported `extern "C"` entry points for the emscripten build. There's no
+//! stdin/file I/O to drive a `main` loop from inside the browser, so the
+//! host JS glue calls these directly: initialize a processor once, feed it
+//! PCM blocks as they arrive, and read denoised blocks back out.
+
+use std::os::raw::c_int;
+use std::slice;
+
+use dtln_rs::dtln_processor::{DtlnDeferredProcessor, DtlnProcessEngine};
+
+static mut PROCESSOR: Option<DtlnDeferredProcessor> = None;
+
+/// Build the default TFLite-backed processor. Must be called once before any
+/// other function here. Returns `0` on success, `-1` if the bundled models
+/// failed to load.
+#[no_mangle]
+pub extern "C" fn dtln_init() -> c_int {
+    match DtlnDeferredProcessor::new() {
+        Ok(processor) => {
+            unsafe { PROCESSOR = Some(processor) };
+            0
+        }
+        Err(e) => {
+            eprintln!("dtln_init failed: {}", e);
+            -1
+        }
+    }
+}
+
+/// Feed one block of interleaved PCM32 samples in, and copy whatever
+/// denoised samples are ready back into `out`. Returns the number of
+/// samples written to `out`, or `-1` on error.
+///
+/// # Safety
+/// `input`/`out` must each point to at least `input_len`/`out_capacity`
+/// valid `i32`s, and `dtln_init` must have succeeded first.
+#[no_mangle]
+pub unsafe extern "C" fn dtln_denoise(
+    input: *const i32,
+    input_len: usize,
+    out: *mut i32,
+    out_capacity: usize,
+) -> c_int {
+    let Some(processor) = (*std::ptr::addr_of_mut!(PROCESSOR)).as_mut() else {
+        eprintln!("dtln_denoise called before dtln_init");
+        return -1;
+    };
+
+    let input = slice::from_raw_parts(input, input_len);
+    let result = match processor.denoise(input) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("dtln_denoise failed: {}", e);
+            return -1;
+        }
+    };
+
+    let written = result.samples.len().min(out_capacity);
+    let out = slice::from_raw_parts_mut(out, out_capacity);
+    out[..written].copy_from_slice(&result.samples[..written]);
+    written as c_int
+}
+
+/// Stop the background inference worker. Safe to call even if `dtln_init`
+/// was never called.
+#[no_mangle]
+pub extern "C" fn dtln_stop() {
+    if let Some(processor) = unsafe { (*std::ptr::addr_of_mut!(PROCESSOR)).as_mut() } {
+        processor.stop();
+    }
+}